@@ -1,32 +1,128 @@
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use anyhow::Result;
+
+use anyhow::{bail, Context, Result};
 use crossterm::event::{self, Event, KeyCode};
 use crossterm::terminal;
 
 use crate::map::{Map, Material};
+use crate::net::{PlayerInput, RollbackSession};
 use crate::player::Player;
 use crate::raycaster;
 use crate::renderer::buffer::FrameBuffer;
 use crate::renderer::terminal::TerminalHandle;
+use crate::sprites::{self, Sprite};
+use crate::textures::TextureSet;
+use crate::water::WaterSurface;
+
+/// Simulation rate. Both peers must run at exactly this rate for the
+/// rollback resimulation to reproduce identical positions.
+const TICK_RATE: f64 = 60.0;
+const DT_FIXED: f64 = 1.0 / TICK_RATE;
+
+/// How many ticks a prediction is allowed to run ahead of the last confirmed
+/// remote input before we'd otherwise need to stall waiting for the network.
+const MAX_PREDICTION: usize = 8;
+
+/// How many ticks of local input delay to apply before a sampled input is
+/// used in simulation. Trades input latency for fewer rollbacks.
+const INPUT_DELAY: usize = 2;
+
+/// Velocity impulse injected into the water surface when the local player
+/// steps into a liquid tile.
+const SPLASH_IMPULSE: f32 = -1.5;
 
 /// The central engine managing game state and the Core Game Loop.
 pub struct Engine {
     map: Map,
-    player: Player,
+    textures: TextureSet,
+    water: WaterSurface,
+    players: [Player; 2],
+    local_player: usize,
+    tick: u64,
+    rollback: RollbackSession,
+    socket: UdpSocket,
+    pending_local_inputs: VecDeque<PlayerInput>,
+    held: PlayerInput,
     terminal: TerminalHandle,
     frame_buffer: FrameBuffer,
     last_time: Instant,
 }
 
 impl Engine {
-    /// Initializes a new game engine session.
+    /// Initializes a new game engine session from CLI flags, binding the
+    /// local UDP socket used for exchanging inputs with the peer. `--player
+    /// <0|1>` picks which slot this copy of the binary plays, `--bind
+    /// <addr>`/`--peer <addr>` pick the local/remote UDP addresses, so the
+    /// two players in a match can run as two separately launched copies of
+    /// the binary. The parsed flags are handed on to `init_networked`.
+    /// Defaults to the slot-0 side of a
+    /// loopback match if none are given. A bare trailing argument is the map
+    /// path, as in chunk0-3, falling back to the built-in default level.
     pub fn init() -> Result<Self> {
+        let mut local_player = 0usize;
+        let mut bind_addr = "0.0.0.0:7777".to_string();
+        let mut peer_addr = "127.0.0.1:7778".to_string();
+        let mut map_path: Option<PathBuf> = None;
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--player" => {
+                    let value = args.next().context("--player requires a value (0 or 1)")?;
+                    local_player = match value.as_str() {
+                        "0" => 0,
+                        "1" => 1,
+                        other => bail!("--player must be 0 or 1, got {other:?}"),
+                    };
+                }
+                "--bind" => bind_addr = args.next().context("--bind requires an address")?,
+                "--peer" => peer_addr = args.next().context("--peer requires an address")?,
+                other => map_path = Some(PathBuf::from(other)),
+            }
+        }
+
+        Self::init_networked(local_player, bind_addr, peer_addr, map_path.as_deref())
+    }
+
+    /// Initializes a session with an explicit player slot, local bind address,
+    /// peer address and optional map path, so each side of a match can be
+    /// started independently.
+    pub fn init_networked(
+        local_player: usize,
+        bind_addr: impl ToSocketAddrs,
+        peer_addr: impl ToSocketAddrs,
+        map_path: Option<&Path>,
+    ) -> Result<Self> {
         let terminal = TerminalHandle::init()?;
         let (cols, rows) = terminal::size()?;
-        
+
+        let socket = UdpSocket::bind(bind_addr).context("Failed to bind netcode UDP socket")?;
+        socket.connect(peer_addr).context("Failed to set netcode peer address")?;
+        socket.set_nonblocking(true).context("Failed to set netcode socket nonblocking")?;
+
+        let (map, players) = match map_path {
+            Some(path) => {
+                let (map, spawn) = Map::from_file(path)?;
+                (map, [Player::at(spawn.position, spawn.angle), Player::at(spawn.position, spawn.angle)])
+            }
+            None => (Map::new(), [Player::new(), Player::new()]),
+        };
+
         Ok(Self {
-            map: Map::new(),
-            player: Player::new(),
+            map,
+            textures: TextureSet::load_default()?,
+            water: WaterSurface::new(cols as usize, 0.0),
+            players,
+            local_player,
+            tick: 0,
+            rollback: RollbackSession::new(local_player, MAX_PREDICTION),
+            socket,
+            pending_local_inputs: VecDeque::from(vec![PlayerInput::empty(); INPUT_DELAY]),
+            held: PlayerInput::empty(),
             terminal,
             frame_buffer: FrameBuffer::new(cols as usize, rows as usize),
             last_time: Instant::now(),
@@ -34,41 +130,51 @@ impl Engine {
     }
 
     /// Starts the main game loop. Blocks until the user exits.
+    ///
+    /// Rendering runs every pass, but the simulation advances in fixed
+    /// `DT_FIXED` steps via an accumulator, so frame-rate hiccups never
+    /// change the outcome of a match: only rendering drops frames, the
+    /// simulation never does.
     pub fn run(&mut self) -> Result<()> {
+        let mut accumulator = Duration::ZERO;
+
         loop {
             let current_time = Instant::now();
             let frame_time = current_time.duration_since(self.last_time).as_secs_f64();
+            accumulator += current_time.duration_since(self.last_time);
             self.last_time = current_time;
 
-            // Handle terminal resizing gracefully
             let (cols, rows) = terminal::size()?;
             self.frame_buffer.resize(cols as usize, rows as usize);
+            self.water.resize(cols as usize);
 
-            // Input Handling (Non-blocking)
-            // Process all pending events to prevent input lag
-            while event::poll(Duration::from_millis(0))? {
-                if let Event::Key(key_event) = event::read()? {
-                    if !self.handle_input(key_event.code, frame_time) {
-                        // User requested exit
-                        self.terminal.cleanup()?;
-                        return Ok(());
-                    }
-                }
+            if !self.poll_local_events()? {
+                self.terminal.cleanup()?;
+                return Ok(());
+            }
+
+            let tick_duration = Duration::from_secs_f64(DT_FIXED);
+            while accumulator >= tick_duration {
+                self.tick()?;
+                accumulator -= tick_duration;
             }
 
-            // Render Preparation
-            self.frame_buffer.clear();
+            // The water surface is cosmetic and animates once per rendered
+            // frame rather than once per fixed sim tick, so it never affects
+            // the deterministic rollback state.
+            self.water.tick();
+            let wave_offsets: Vec<f32> = (0..self.frame_buffer.width).map(|x| self.water.offset_at(x)).collect();
+            self.frame_buffer.clear(&wave_offsets);
+            let local = &self.players[self.local_player];
+            raycaster::render_frame(local, &self.map, &self.textures, &mut self.frame_buffer);
 
-            // Core Raycasting
-            raycaster::render_frame(&self.player, &self.map, &mut self.frame_buffer);
+            let remote = self.players[1 - self.local_player];
+            let other_player = [Sprite { position: remote.position, glyph: '☻', color: crossterm::style::Color::Yellow }];
+            sprites::render_sprites(local, &other_player, &mut self.frame_buffer);
 
-            // FPS Overlay UI
             self.draw_fps_counter(frame_time);
-
-            // Output Flush
             self.frame_buffer.render(&mut self.terminal.stdout)?;
 
-            // Performance Management (Cap to ~60 FPS so we don't melt the CPU)
             let elapsed = current_time.elapsed();
             let target_frame_time = Duration::from_micros(16666);
             if elapsed < target_frame_time {
@@ -77,61 +183,186 @@ impl Engine {
         }
     }
 
-    /// Processes keyboard input. Returns `false` if the engine should terminate.
-    fn handle_input(&mut self, key: KeyCode, frame_time: f64) -> bool {
-        match key {
-            KeyCode::Esc | KeyCode::Char('q') => return false,
-            
-            // Forward Movement
-            KeyCode::Char('w') | KeyCode::Up => {
-                let new_x = self.player.position.x + self.player.direction.x * self.player.move_speed * frame_time;
-                if self.map.get(new_x as usize, self.player.position.y as usize) == Material::Empty {
-                    self.player.position.x = new_x;
-                }
-                let new_y = self.player.position.y + self.player.direction.y * self.player.move_speed * frame_time;
-                if self.map.get(self.player.position.x as usize, new_y as usize) == Material::Empty {
-                    self.player.position.y = new_y;
-                }
-            },
-            
-            // Backward Movement
-            KeyCode::Char('s') | KeyCode::Down => {
-                let new_x = self.player.position.x - self.player.direction.x * self.player.move_speed * frame_time;
-                if self.map.get(new_x as usize, self.player.position.y as usize) == Material::Empty {
-                    self.player.position.x = new_x;
-                }
-                let new_y = self.player.position.y - self.player.direction.y * self.player.move_speed * frame_time;
-                if self.map.get(self.player.position.x as usize, new_y as usize) == Material::Empty {
-                    self.player.position.y = new_y;
+    /// Advances the simulation by exactly one fixed tick: samples local
+    /// input, sends it to the peer, predicts the remote input if the real
+    /// one hasn't arrived yet, then steps both players deterministically.
+    /// Any now-confirmed remote inputs that disagreed with a past prediction
+    /// trigger a rollback and resimulation up to the present tick.
+    fn tick(&mut self) -> Result<()> {
+        // `self.held` isn't cleared here: `run`'s accumulator can call
+        // `tick` more than once per rendered frame when rendering falls
+        // behind, and a key that's still physically down should still be
+        // applied to every one of those catch-up ticks. It's only reset in
+        // `poll_local_events`, once per frame.
+        self.pending_local_inputs.push_back(self.held);
+        let delayed_local_input = self.pending_local_inputs.pop_front().unwrap_or_default();
+        self.send_input(self.tick, delayed_local_input)?;
+
+        let mut inputs = [PlayerInput::empty(); 2];
+        inputs[self.local_player] = delayed_local_input;
+        inputs[1 - self.local_player] = self.rollback.predicted_remote_input();
+
+        let local_position = self.players[self.local_player].position;
+        let was_in_water = self.map.get(local_position.x as usize, local_position.y as usize) == Material::Water;
+
+        self.rollback.save(self.tick, self.players, inputs);
+        advance(&mut self.players, &self.map, inputs, DT_FIXED);
+        self.tick += 1;
+
+        let local_position = self.players[self.local_player].position;
+        let now_in_water = self.map.get(local_position.x as usize, local_position.y as usize) == Material::Water;
+        if now_in_water && !was_in_water {
+            // This raycaster has no floor-casting, so there's no real mapping
+            // from the player's world position to a screen column; splashing
+            // the center column is a reasonable stand-in for "just ahead".
+            self.water.splash(self.frame_buffer.width / 2, SPLASH_IMPULSE);
+        }
+
+        while let Some((remote_tick, real_input)) = self.recv_input()? {
+            if let Some(rollback_to) = self.rollback.confirm_remote_input(remote_tick, real_input) {
+                self.resimulate_from(rollback_to);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores the saved state as of `from_tick` and replays every tick
+    /// since using the now-corrected input history, landing back on the
+    /// current tick with an identical result to the peer.
+    ///
+    /// Each replayed tick's corrected pre-tick state is written back into
+    /// `rollback`'s history as it goes, not just into `self.players`: a
+    /// later rollback landing on a tick this resimulation already passed
+    /// through must restore from the corrected snapshot, or the two peers'
+    /// positions can permanently diverge once ordinary UDP jitter produces
+    /// a second out-of-order correction.
+    fn resimulate_from(&mut self, from_tick: u64) {
+        let Some((mut players, inputs)) = self.rollback.restore_from(from_tick) else {
+            return;
+        };
+        for (offset, inputs) in inputs.into_iter().enumerate() {
+            self.rollback.overwrite_state(from_tick + offset as u64, players);
+            advance(&mut players, &self.map, inputs, DT_FIXED);
+        }
+        self.players = players;
+    }
+
+    /// Sends this tick's local input to the peer as a 9-byte packet: the
+    /// tick count (u64 little-endian) followed by the packed input byte.
+    fn send_input(&self, tick: u64, input: PlayerInput) -> Result<()> {
+        let mut packet = [0u8; 9];
+        packet[0..8].copy_from_slice(&tick.to_le_bytes());
+        packet[8] = input.to_byte();
+        match self.socket.send(&packet) {
+            Ok(_) => Ok(()),
+            // The peer may not be listening yet; the error is ignored and this
+            // tick's input is not resent (each tick sends only its own input).
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => Ok(()),
+            Err(e) => Err(e).context("Failed to send netcode input packet"),
+        }
+    }
+
+    /// Drains one pending remote input packet off the socket, if any.
+    fn recv_input(&self) -> Result<Option<(u64, PlayerInput)>> {
+        let mut packet = [0u8; 9];
+        match self.socket.recv(&mut packet) {
+            Ok(9) => {
+                let tick = u64::from_le_bytes(packet[0..8].try_into().unwrap());
+                Ok(Some((tick, PlayerInput::from_byte(packet[8]))))
+            }
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e).context("Failed to receive netcode input packet"),
+        }
+    }
+
+    /// Processes pending keyboard events, updating the locally-held input
+    /// bitflags. Returns `false` if the engine should terminate.
+    ///
+    /// Runs once per rendered frame, so this is also where `held` gets
+    /// cleared before being rebuilt from this frame's events: terminals
+    /// don't give us real key-up events, but a key still physically held
+    /// down keeps re-arriving as a new press each frame via OS key-repeat,
+    /// which is what lets `held` track "currently down" well enough.
+    fn poll_local_events(&mut self) -> Result<bool> {
+        self.held = PlayerInput::empty();
+        while event::poll(Duration::from_millis(0))? {
+            if let Event::Key(key_event) = event::read()? {
+                if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q')) {
+                    return Ok(false);
                 }
-            },
-
-            // Rotation
-            KeyCode::Right | KeyCode::Char('d') => {
-                self.player.rotate(-self.player.rot_speed * frame_time);
-            },
-            KeyCode::Left | KeyCode::Char('a') => {
-                self.player.rotate(self.player.rot_speed * frame_time);
+                self.apply_key(key_event.code);
             }
-            _ => {}
         }
-        true
+        Ok(true)
+    }
+
+    /// Updates `self.held` from a single key, translating it into the
+    /// packed `PlayerInput` bitflags consumed by `advance`.
+    fn apply_key(&mut self, key: KeyCode) {
+        let (flag, pressed) = match key {
+            KeyCode::Char('w') | KeyCode::Up => (PlayerInput::FORWARD, true),
+            KeyCode::Char('s') | KeyCode::Down => (PlayerInput::BACKWARD, true),
+            KeyCode::Left | KeyCode::Char('a') => (PlayerInput::TURN_LEFT, true),
+            KeyCode::Right | KeyCode::Char('d') => (PlayerInput::TURN_RIGHT, true),
+            _ => return,
+        };
+        // Keys arrive as discrete press events rather than a held state, so treat
+        // a key event as "pressed" until `poll_local_events` clears `held` at the
+        // start of the next rendered frame; good enough fidelity at a 60Hz poll rate.
+        if pressed {
+            self.held.insert(flag);
+        }
     }
 
     /// Overlays current Frames Per Second counter to the buffer
     fn draw_fps_counter(&mut self, frame_time: f64) {
         let fps = if frame_time > 0.0 { 1.0 / frame_time } else { 0.0 };
         let fps_str = format!(" FPS: {:.0} ", fps);
-        let mut x_offset = 0;
-        for ch in fps_str.chars() {
+        for (x_offset, ch) in fps_str.chars().enumerate() {
             self.frame_buffer.set(
-                x_offset, 
-                0, 
-                ch, 
-                crossterm::style::Color::White, 
-                crossterm::style::Color::AnsiValue(236) // Dark Gray Background
+                x_offset,
+                0,
+                ch,
+                crossterm::style::Color::White,
+                crossterm::style::Color::AnsiValue(236), // Dark Gray Background
             );
-            x_offset += 1;
+        }
+    }
+}
+
+/// Steps both players forward by one deterministic fixed tick given this
+/// tick's packed inputs. Used both for the live path and for rollback
+/// resimulation, so it must only ever depend on `inputs`, `map` and
+/// `dt_fixed` -- never on wall-clock time -- to guarantee both peers compute
+/// identical `Vector2D` positions.
+fn advance(players: &mut [Player; 2], map: &Map, inputs: [PlayerInput; 2], dt_fixed: f64) {
+    for (player, input) in players.iter_mut().zip(inputs.iter()) {
+        if input.contains(PlayerInput::TURN_RIGHT) {
+            player.rotate(-player.rot_speed * dt_fixed);
+        }
+        if input.contains(PlayerInput::TURN_LEFT) {
+            player.rotate(player.rot_speed * dt_fixed);
+        }
+
+        let mut facing = 0.0;
+        if input.contains(PlayerInput::FORWARD) {
+            facing += 1.0;
+        }
+        if input.contains(PlayerInput::BACKWARD) {
+            facing -= 1.0;
+        }
+        if facing == 0.0 {
+            continue;
+        }
+
+        let new_x = player.position.x + player.direction.x * player.move_speed * dt_fixed * facing;
+        if map.get(new_x as usize, player.position.y as usize).is_walkable() {
+            player.position.x = new_x;
+        }
+        let new_y = player.position.y + player.direction.y * player.move_speed * dt_fixed * facing;
+        if map.get(player.position.x as usize, new_y as usize).is_walkable() {
+            player.position.y = new_y;
         }
     }
 }
@@ -1,9 +1,13 @@
 pub mod engine;
 pub mod map;
 pub mod math;
+pub mod net;
 pub mod player;
 pub mod raycaster;
 pub mod renderer;
+pub mod sprites;
+pub mod textures;
+pub mod water;
 
 use anyhow::Result;
 
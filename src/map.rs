@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::math::Vector2D;
+
+/// A block height expressed as a fraction of a full floor-to-ceiling wall,
+/// used by `Material::LowWall` and `Material::Slope`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeightFraction {
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+impl HeightFraction {
+    pub fn as_f64(self) -> f64 {
+        match self {
+            HeightFraction::Quarter => 0.25,
+            HeightFraction::Half => 0.5,
+            HeightFraction::ThreeQuarters => 0.75,
+        }
+    }
+}
+
+/// The material a single map tile is made of. Determines wall color/texture
+/// during raycasting and whether a tile blocks movement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Material {
+    Empty,
+    SolidWall,
+    BrickWall,
+    StoneWall,
+    WoodWall,
+    /// A rippling liquid tile. Walkable like `Empty`, but its surface is
+    /// animated by a `water::WaterSurface`.
+    Water,
+    /// A step or low wall that doesn't reach the ceiling. Rays may pass over
+    /// it and hit something farther away, which `raycaster::render_frame`
+    /// composites behind it.
+    LowWall(HeightFraction),
+    /// A ramp whose height interpolates linearly from `low` to `high` across
+    /// the tile, in the direction the ray's fractional hit coordinate runs.
+    Slope { low: HeightFraction, high: HeightFraction },
+    /// Returned for any coordinate outside the grid, treated as solid so the
+    /// DDA loop always terminates.
+    OutOfBounds,
+}
+
+impl Material {
+    /// Whether a player (or a ray) can pass through this tile. Low walls and
+    /// slopes don't reach the ceiling, but they're still solid obstacles a
+    /// player collides with, just like a full wall.
+    pub fn is_walkable(self) -> bool {
+        matches!(self, Material::Empty | Material::Water)
+    }
+
+    /// Whether the DDA loop in `raycaster::render_frame` should keep
+    /// stepping past this tile looking for something farther away, rather
+    /// than treating it as the occluding hit.
+    pub fn is_low(self) -> bool {
+        matches!(self, Material::LowWall(_) | Material::Slope { .. })
+    }
+}
+
+const GRID: [[u8; 24]; 25] = [
+    [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+    [1, 0, 0, 0, 0, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 2, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 2, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0, 3, 3, 3, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 2, 0, 2, 2, 2, 0, 0, 0, 0, 0, 3, 0, 0, 0, 3, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 1],
+    [1, 0, 4, 4, 4, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 1],
+    [1, 0, 4, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 3, 0, 0, 0, 1],
+    [1, 0, 4, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 4, 4, 4, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 5, 5, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 5, 5, 0, 0, 0, 1],
+    [1, 0, 0, 0, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 4, 4, 4, 0, 0, 1],
+    [1, 0, 0, 0, 2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 4, 0, 0, 1],
+    [1, 0, 0, 0, 2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 4, 0, 0, 1],
+    [1, 0, 0, 0, 2, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 4, 4, 4, 0, 0, 1],
+    [1, 0, 0, 0, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 3, 3, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 3, 0, 6, 0, 7, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 3, 3, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+];
+
+fn material_for(code: u8) -> Material {
+    match code {
+        1 => Material::SolidWall,
+        2 => Material::BrickWall,
+        3 => Material::StoneWall,
+        4 => Material::WoodWall,
+        5 => Material::Water,
+        6 => Material::LowWall(HeightFraction::Half),
+        7 => Material::Slope { low: HeightFraction::Quarter, high: HeightFraction::ThreeQuarters },
+        _ => Material::Empty,
+    }
+}
+
+/// The readable key each `Material` is written as in a JSON5 map file's rows,
+/// e.g. `"#.....BB..."`. Kept as a single table so the mapping can't drift
+/// between loading and authoring docs.
+const MATERIAL_KEYS: &[(char, Material)] = &[
+    ('.', Material::Empty),
+    ('#', Material::SolidWall),
+    ('B', Material::BrickWall),
+    ('S', Material::StoneWall),
+    ('W', Material::WoodWall),
+    ('~', Material::Water),
+    ('l', Material::LowWall(HeightFraction::Half)),
+    ('/', Material::Slope { low: HeightFraction::Quarter, high: HeightFraction::ThreeQuarters }),
+];
+
+fn material_for_key(key: char) -> Option<Material> {
+    MATERIAL_KEYS.iter().find(|(k, _)| *k == key).map(|(_, m)| *m)
+}
+
+/// The player's spawn point, as declared by a map file.
+pub struct Spawn {
+    pub position: Vector2D,
+    pub angle: f64,
+}
+
+#[derive(Deserialize)]
+struct SpawnDoc {
+    x: f64,
+    y: f64,
+}
+
+/// The on-disk JSON5 shape of a map file: a grid of `MATERIAL_KEYS` characters
+/// plus where the player should spawn.
+#[derive(Deserialize)]
+struct MapDoc {
+    rows: Vec<String>,
+    spawn: SpawnDoc,
+    #[serde(default)]
+    spawn_angle: f64,
+}
+
+/// The static level grid that walls are raycast against.
+pub struct Map {
+    width: usize,
+    height: usize,
+    tiles: Vec<Material>,
+}
+
+impl Map {
+    /// Builds the built-in default level.
+    pub fn new() -> Self {
+        let height = GRID.len();
+        let width = GRID[0].len();
+        let tiles = GRID.iter().flat_map(|row| row.iter().map(|&c| material_for(c))).collect();
+
+        Self { width, height, tiles }
+    }
+
+    /// Loads a level from a JSON5 map file (see `MATERIAL_KEYS` for the tile
+    /// alphabet), returning the map and the spawn point it declares.
+    ///
+    /// Rejects a non-rectangular grid or one that isn't fully bordered by
+    /// non-`Empty` tiles, since an open edge would let the DDA loop in
+    /// `raycaster::render_frame` walk off the array.
+    pub fn from_file(path: &Path) -> Result<(Self, Spawn)> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read map file {}", path.display()))?;
+        let doc: MapDoc = json5::from_str(&text)
+            .with_context(|| format!("failed to parse map file {}", path.display()))?;
+
+        let height = doc.rows.len();
+        let width = doc.rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 || height == 0 {
+            bail!("map file {} has an empty grid ({width}x{height})", path.display());
+        }
+
+        let mut tiles = Vec::with_capacity(width * height);
+        for (y, row) in doc.rows.iter().enumerate() {
+            let chars: Vec<char> = row.chars().collect();
+            if chars.len() != width {
+                bail!(
+                    "map row {y} has {} tiles but row 0 has {width}: {row:?}",
+                    chars.len()
+                );
+            }
+            for key in chars {
+                let material = material_for_key(key)
+                    .with_context(|| format!("map row {y} has unknown tile key '{key}'"))?;
+                tiles.push(material);
+            }
+        }
+
+        let map = Self { width, height, tiles };
+        map.validate_bordered()?;
+
+        let spawn = Spawn {
+            position: Vector2D::new(doc.spawn.x, doc.spawn.y),
+            angle: doc.spawn_angle,
+        };
+        Ok((map, spawn))
+    }
+
+    /// Returns the material at `(x, y)`, or `Material::OutOfBounds` if outside the grid.
+    pub fn get(&self, x: usize, y: usize) -> Material {
+        if x >= self.width || y >= self.height {
+            return Material::OutOfBounds;
+        }
+        self.tiles[y * self.width + x]
+    }
+
+    /// Checks that every edge tile fully occludes, so DDA can never step
+    /// outside the grid. A `LowWall`/`Slope` isn't enough here even though
+    /// it's `!is_walkable()`: the DDA loop in `raycaster::render_frame`
+    /// steps straight past `is_low()` tiles looking for whatever's behind
+    /// them, so a border made of those wouldn't actually stop it.
+    fn validate_bordered(&self) -> Result<()> {
+        for x in 0..self.width {
+            if !self.occludes(x, 0) {
+                bail!("map row 0 is not bordered at column {x}");
+            }
+            if !self.occludes(x, self.height - 1) {
+                bail!("map row {} is not bordered at column {x}", self.height - 1);
+            }
+        }
+        for y in 0..self.height {
+            if !self.occludes(0, y) {
+                bail!("map row {y} is not bordered at column 0");
+            }
+            if !self.occludes(self.width - 1, y) {
+                bail!("map row {y} is not bordered at column {}", self.width - 1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the tile at `(x, y)` fully occludes the DDA loop, i.e. is
+    /// neither walkable floor nor a `LowWall`/`Slope` it would step past.
+    fn occludes(&self, x: usize, y: usize) -> bool {
+        let material = self.get(x, y);
+        !material.is_walkable() && !material.is_low()
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,133 @@
+//! Rollback netcode support: the packed input type sent over the wire and the
+//! rollback history used to resimulate ticks once a remote input is confirmed.
+
+use std::collections::VecDeque;
+
+use bitflags::bitflags;
+use bytemuck::{Pod, Zeroable};
+
+use crate::player::Player;
+
+bitflags! {
+    /// The buttons held by one player during a single fixed-step tick, packed
+    /// into a single byte so it can be sent as raw bytes over UDP.
+    #[derive(Default, Clone, Copy, PartialEq, Eq)]
+    #[repr(transparent)]
+    pub struct PlayerInput: u8 {
+        const FORWARD    = 0b0001;
+        const BACKWARD   = 0b0010;
+        const TURN_LEFT  = 0b0100;
+        const TURN_RIGHT = 0b1000;
+    }
+}
+
+// SAFETY: `PlayerInput` is `#[repr(transparent)]` over a single `u8`, so any
+// bit pattern is a valid value and the all-zero pattern is `PlayerInput::empty()`.
+unsafe impl Pod for PlayerInput {}
+unsafe impl Zeroable for PlayerInput {}
+
+impl PlayerInput {
+    /// Serializes to the single byte sent as the packet payload, via its
+    /// `Pod` representation rather than reaching into `bits()` by hand.
+    pub fn to_byte(self) -> u8 {
+        bytemuck::bytes_of(&self)[0]
+    }
+
+    /// Deserializes a packet payload byte back into a `PlayerInput`.
+    pub fn from_byte(byte: u8) -> Self {
+        *bytemuck::from_bytes(std::slice::from_ref(&byte))
+    }
+}
+
+/// Everything needed to resimulate from a given tick: both players' full
+/// state plus the tick count itself. `Map` is static so it is never saved.
+#[derive(Clone, Copy)]
+struct SavedState {
+    tick: u64,
+    players: [Player; 2],
+}
+
+/// Tracks the last `max_prediction` ticks of state and input so a late or
+/// mispredicted remote input can be reconciled by rolling back and
+/// resimulating forward, rather than ever stalling the local simulation.
+pub struct RollbackSession {
+    local_player: usize,
+    max_prediction: usize,
+    history: VecDeque<SavedState>,
+    inputs: VecDeque<[PlayerInput; 2]>,
+}
+
+impl RollbackSession {
+    /// Creates a session tracking up to `max_prediction` ticks of rollback history.
+    pub fn new(local_player: usize, max_prediction: usize) -> Self {
+        Self {
+            local_player,
+            max_prediction,
+            history: VecDeque::with_capacity(max_prediction + 1),
+            inputs: VecDeque::with_capacity(max_prediction + 1),
+        }
+    }
+
+    /// Records the state `players` was in *before* `inputs` was applied for `tick`,
+    /// evicting history older than `max_prediction` ticks.
+    pub fn save(&mut self, tick: u64, players: [Player; 2], inputs: [PlayerInput; 2]) {
+        self.history.push_back(SavedState { tick, players });
+        self.inputs.push_back(inputs);
+        while self.history.len() > self.max_prediction + 1 {
+            self.history.pop_front();
+            self.inputs.pop_front();
+        }
+    }
+
+    /// The remote player's most recent input, used to predict ticks whose
+    /// real packet hasn't arrived yet (repeat-last prediction).
+    pub fn predicted_remote_input(&self) -> PlayerInput {
+        let remote = 1 - self.local_player;
+        self.inputs.back().map(|i| i[remote]).unwrap_or_default()
+    }
+
+    /// Confirms the real remote input for `tick`. Returns `Some(tick)` to
+    /// roll back to if the prediction we used for that tick was wrong;
+    /// returns `None` if the prediction held (or `tick` has already aged
+    /// out of the history and can no longer be corrected).
+    pub fn confirm_remote_input(&mut self, tick: u64, real_input: PlayerInput) -> Option<u64> {
+        let remote = 1 - self.local_player;
+        let idx = self.index_of(tick)?;
+
+        let predicted = self.inputs[idx][remote];
+        self.inputs[idx][remote] = real_input;
+        if predicted == real_input {
+            return None;
+        }
+        Some(tick)
+    }
+
+    /// The saved `players` state as of `tick`, along with every input recorded
+    /// since (in order), for use while resimulating forward.
+    pub fn restore_from(&self, tick: u64) -> Option<([Player; 2], Vec<[PlayerInput; 2]>)> {
+        let idx = self.index_of(tick)?;
+        let players = self.history[idx].players;
+        let inputs = self.inputs.iter().skip(idx).copied().collect();
+        Some((players, inputs))
+    }
+
+    /// Overwrites the saved pre-tick state recorded for `tick` with a
+    /// corrected one. Used while resimulating so a rollback that reaches
+    /// into a range an earlier rollback already resimulated restores from
+    /// the corrected snapshot rather than the stale, now-wrong one that was
+    /// saved the first time that tick was simulated.
+    pub fn overwrite_state(&mut self, tick: u64, players: [Player; 2]) {
+        if let Some(idx) = self.index_of(tick) {
+            self.history[idx].players = players;
+        }
+    }
+
+    fn index_of(&self, tick: u64) -> Option<usize> {
+        let oldest = self.history.front()?.tick;
+        if tick < oldest {
+            return None;
+        }
+        let idx = (tick - oldest) as usize;
+        (idx < self.history.len()).then_some(idx)
+    }
+}
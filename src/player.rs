@@ -1,6 +1,7 @@
 use crate::math::Vector2D;
 
 /// Represents the player entity navigating the map.
+#[derive(Clone, Copy)]
 pub struct Player {
     /// The absolute position of the player in the map grid.
     pub position: Vector2D,
@@ -26,6 +27,19 @@ impl Player {
         }
     }
 
+    /// Creates a player at an explicit spawn position and facing angle (in
+    /// radians), as declared by a loaded `Map` file.
+    pub fn at(position: Vector2D, angle: f64) -> Self {
+        let direction = Vector2D::new(angle.cos(), angle.sin());
+        let camera_plane = direction.rotate(-std::f64::consts::FRAC_PI_2) * 0.66; // ~66 degree FOV
+        Self {
+            position,
+            direction,
+            camera_plane,
+            ..Self::new()
+        }
+    }
+
     /// Rotates the player's camera by the given rotation amount (in radians).
     /// Positive `rot_amt` rotates the camera right (clockwise in a top-down view).
     pub fn rotate(&mut self, rot_amt: f64) {
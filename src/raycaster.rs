@@ -1,12 +1,23 @@
 use crate::map::{Map, Material};
-use crate::math::Vector2D;
 use crate::player::Player;
 use crate::renderer::buffer::FrameBuffer;
+use crate::textures::{TextureSet, TEX_SIZE};
 use crossterm::style::Color;
 
+/// A tile the ray passed over (a `LowWall` or `Slope`) before reaching
+/// whatever finally occluded the column, recorded so it can be composited
+/// once the DDA loop finishes. A ray may pass over several of these in a
+/// row, so they're collected rather than keeping only the first.
+struct LowHit {
+    material: Material,
+    side: i32,
+    perp_wall_dist: f64,
+    wall_x: f64,
+}
+
 /// Executes the Digital Differential Analysis (DDA) raycasting algorithm
 /// for every vertical column of the screen and draws it to the FrameBuffer.
-pub fn render_frame(player: &Player, map: &Map, frame: &mut FrameBuffer) {
+pub fn render_frame(player: &Player, map: &Map, textures: &TextureSet, frame: &mut FrameBuffer) {
     let screen_width = frame.width;
     let screen_height = frame.height as f64;
 
@@ -26,15 +37,19 @@ pub fn render_frame(player: &Player, map: &Map, frame: &mut FrameBuffer) {
         // Length of ray from one x or y-side to next x or y-side
         let delta_dist_x = if ray_dir.x == 0.0 { f64::MAX } else { (1.0 / ray_dir.x).abs() };
         let delta_dist_y = if ray_dir.y == 0.0 { f64::MAX } else { (1.0 / ray_dir.y).abs() };
-        let perp_wall_dist: f64;
 
         // What direction to step in x or y-direction (either +1 or -1)
         let step_x: i32;
         let step_y: i32;
 
-        let mut hit = false; // Was there a wall hit?
+        let mut hit = false; // Was there an occluding wall hit?
         let mut side = 0; // Was a NS or a EW wall hit?
         let mut hit_material = Material::Empty;
+        // Every low tile the ray passes over before something finally
+        // occludes it, nearest first, so more than one short wall in a row
+        // (e.g. two adjacent low walls in a corridor) each still get drawn
+        // instead of only the first.
+        let mut low_hits: Vec<LowHit> = Vec::new();
 
         // Calculate step and initial side_dist
         if ray_dir.x < 0.0 {
@@ -64,69 +79,232 @@ pub fn render_frame(player: &Player, map: &Map, frame: &mut FrameBuffer) {
                 map_y += step_y;
                 side = 1;
             }
-            
-            // Check if ray has hit a wall
+
             hit_material = map.get(map_x as usize, map_y as usize);
-            if hit_material != Material::Empty {
+
+            if hit_material.is_low() {
+                // A short wall never occludes the column by itself: keep
+                // stepping for whatever's behind it, remembering every one
+                // passed over so each can be drawn in front of that later.
+                let (perp_wall_dist, wall_x) =
+                    hit_geometry(player, ray_dir, map_x, map_y, step_x, step_y, side);
+                low_hits.push(LowHit { material: hit_material, side, perp_wall_dist, wall_x });
+            } else if !hit_material.is_walkable() {
                 hit = true;
             }
         }
 
-        // Calculate distance projected on camera direction
-        if side == 0 {
-            perp_wall_dist = (map_x as f64 - player.position.x + (1.0 - step_x as f64) / 2.0) / ray_dir.x;
-        } else {
-            perp_wall_dist = (map_y as f64 - player.position.y + (1.0 - step_y as f64) / 2.0) / ray_dir.y;
+        let (perp_wall_dist, wall_x) = hit_geometry(player, ray_dir, map_x, map_y, step_x, step_y, side);
+
+        // Draw the far, fully-occluding wall first as the background.
+        let full_line_height = screen_height / perp_wall_dist;
+        let (draw_start, draw_end, unclamped_start, unclamped_height) = centered_span(full_line_height, frame.height);
+        let flip = flip_tex_x(ray_dir, side);
+        draw_segment(
+            x, draw_start, draw_end, hit_material, side, perp_wall_dist, wall_x, flip,
+            unclamped_start, unclamped_height, 1.0, textures, frame,
+        );
+
+        // Then composite every short wall the ray passed over on top of it,
+        // farthest first, so the nearest one ends up on top as it should.
+        for low in low_hits.iter().rev() {
+            let low_line_height = screen_height / low.perp_wall_dist;
+            let fraction = height_fraction(low.material, low.wall_x);
+            let (draw_start, draw_end, unclamped_start, unclamped_height) =
+                floor_span(low_line_height, fraction, frame.height);
+            let flip = flip_tex_x(ray_dir, low.side);
+            draw_segment(
+                x, draw_start, draw_end, low.material, low.side, low.perp_wall_dist, low.wall_x, flip,
+                unclamped_start, unclamped_height, fraction, textures, frame,
+            );
         }
 
-        // Calculate height of line to draw on screen
-        let line_height = (screen_height / perp_wall_dist) as i32;
+        // The depth buffer records whatever is nearest to the player in this
+        // column, short walls included, so a sprite behind a short wall is
+        // hidden over the column's whole height, above the wall's top too.
+        let nearest_dist = low_hits.first().map_or(perp_wall_dist, |low| low.perp_wall_dist);
+        frame.set_depth(x, nearest_dist);
+    }
+}
+
+/// Computes `(perp_wall_dist, wall_x)` for a ray that has just stepped into
+/// `(map_x, map_y)`: the perpendicular distance from the camera plane used
+/// to scale wall height, and the fractional coordinate across the tile's
+/// hit face, used for texture and slope-height interpolation.
+fn hit_geometry(
+    player: &Player,
+    ray_dir: crate::math::Vector2D,
+    map_x: i32,
+    map_y: i32,
+    step_x: i32,
+    step_y: i32,
+    side: i32,
+) -> (f64, f64) {
+    let perp_wall_dist = if side == 0 {
+        (map_x as f64 - player.position.x + (1.0 - step_x as f64) / 2.0) / ray_dir.x
+    } else {
+        (map_y as f64 - player.position.y + (1.0 - step_y as f64) / 2.0) / ray_dir.y
+    };
+
+    let wall_x = if side == 0 {
+        player.position.y + perp_wall_dist * ray_dir.y
+    } else {
+        player.position.x + perp_wall_dist * ray_dir.x
+    };
+    (perp_wall_dist, wall_x - wall_x.floor())
+}
+
+/// Whether the texture column for a tile hit on `side` needs flipping so
+/// textures read the right way round regardless of which direction the ray
+/// approached from.
+fn flip_tex_x(ray_dir: crate::math::Vector2D, side: i32) -> bool {
+    (side == 0 && ray_dir.x > 0.0) || (side == 1 && ray_dir.y < 0.0)
+}
 
-        // Calculate lowest and highest pixel to fill in current stripe
-        let mut draw_start = -line_height / 2 + frame.height as i32 / 2;
-        if draw_start < 0 {
-            draw_start = 0;
+/// The fraction of a full wall's height this tile should be drawn at: 1.0
+/// for ordinary walls, a fixed fraction for `LowWall`, and a linear
+/// interpolation between `low` and `high` across the tile for `Slope`.
+fn height_fraction(material: Material, wall_x: f64) -> f64 {
+    match material {
+        Material::LowWall(height) => height.as_f64(),
+        Material::Slope { low, high } => {
+            low.as_f64() + (high.as_f64() - low.as_f64()) * wall_x
         }
-        let mut draw_end = line_height / 2 + frame.height as i32 / 2;
-        if draw_end >= frame.height as i32 {
-            draw_end = frame.height as i32 - 1;
+        _ => 1.0,
+    }
+}
+
+/// The `(draw_start, draw_end, unclamped_draw_start, unclamped_height)` for a
+/// wall centered on the horizon, the way a full floor-to-ceiling wall has
+/// always been drawn here. The unclamped values describe the slice's true
+/// position and size before being clipped to the screen, so a clipped stripe
+/// still samples the correct cropped slice of its texture.
+fn centered_span(line_height: f64, screen_height: usize) -> (i32, i32, i32, i32) {
+    let line_height = line_height as i32;
+    let half = screen_height as i32 / 2;
+    let unclamped_draw_start = half - line_height / 2;
+    let unclamped_draw_end = half + line_height / 2;
+    let draw_start = unclamped_draw_start.max(0);
+    let draw_end = unclamped_draw_end.min(screen_height as i32 - 1);
+    (draw_start, draw_end, unclamped_draw_start, line_height)
+}
+
+/// The `(draw_start, draw_end, unclamped_draw_start, unclamped_height)` for a
+/// partial-height tile: its floor-level bottom matches where a full wall at
+/// the same distance would meet the floor, but its top is pulled down by
+/// `fraction` so it sits on the floor instead of being centered on the
+/// horizon. As with `centered_span`, the unclamped values describe the
+/// slice's true extent so a clipped stripe samples the correct texture slice.
+fn floor_span(full_line_height: f64, fraction: f64, screen_height: usize) -> (i32, i32, i32, i32) {
+    let full_line_height = full_line_height as i32;
+    let half = screen_height as i32 / 2;
+    let unclamped_draw_end = half + full_line_height / 2;
+    let draw_end = unclamped_draw_end.min(screen_height as i32 - 1);
+    let scaled_height = (full_line_height as f64 * fraction) as i32;
+    let unclamped_draw_start = unclamped_draw_end - scaled_height;
+    let draw_start = unclamped_draw_start.max(0);
+    (draw_start, draw_end, unclamped_draw_start, scaled_height)
+}
+
+/// Draws one column's worth of wall, either sampling a loaded texture or
+/// falling back to the flat distance-shaded color/glyph this raycaster
+/// always used. `final_dim` additionally darkens a short wall's already
+/// distance-dimmed color a bit further so it doesn't read as the same
+/// brightness as a nearer full wall.
+#[allow(clippy::too_many_arguments)]
+fn draw_segment(
+    x: usize,
+    draw_start: i32,
+    draw_end: i32,
+    hit_material: Material,
+    side: i32,
+    perp_wall_dist: f64,
+    wall_x: f64,
+    flip_tex_x: bool,
+    unclamped_draw_start: i32,
+    unclamped_height: i32,
+    height_scale: f64,
+    textures: &TextureSet,
+    frame: &mut FrameBuffer,
+) {
+    // Make shadows by dimming colors (dist and side based)
+    let dim_factor = if side == 1 { 0.7 } else { 1.0 }; // N/S vs E/W
+    let dist_dim = (1.0 - (perp_wall_dist / 20.0)).max(0.1);
+    let final_dim = dim_factor * dist_dim * height_scale.sqrt().max(0.5);
+
+    match textures.get(hit_material) {
+        Some(texture) => {
+            let mut tex_x = (wall_x * TEX_SIZE as f64) as usize;
+            if flip_tex_x {
+                tex_x = TEX_SIZE - tex_x - 1;
+            }
+
+            // Map against the slice's true (unclamped) position and height,
+            // not just what survived clipping to the screen, so a stripe
+            // clipped by the screen edge shows the correctly cropped slice
+            // of the texture instead of squeezing the whole thing in.
+            let unclamped_height = unclamped_height.max(1);
+            for y in draw_start..draw_end {
+                let tex_y = (((y - unclamped_draw_start) * TEX_SIZE as i32) / unclamped_height)
+                    .clamp(0, TEX_SIZE as i32 - 1) as usize;
+                let (r, g, b) = match texture.sample(tex_x, tex_y) {
+                    Color::Rgb { r, g, b } => (r, g, b),
+                    _ => (180, 180, 180),
+                };
+                let (dr, dg, db) = (r as f64 * final_dim, g as f64 * final_dim, b as f64 * final_dim);
+                let dimmed = Color::Rgb { r: dr as u8, g: dg as u8, b: db as u8 };
+                let luminance = 0.299 * dr + 0.587 * dg + 0.114 * db;
+                let ch = glyph_for_luminance(luminance);
+                frame.set(x, y as usize, ch, dimmed, Color::Reset);
+            }
         }
+        None => {
+            let (r, g, b) = match hit_material {
+                Material::SolidWall => (180, 0, 0),    // Red
+                Material::BrickWall => (0, 180, 0),    // Green
+                Material::StoneWall => (0, 0, 180),    // Blue
+                Material::WoodWall => (180, 180, 180), // White
+                Material::LowWall(_) => (150, 140, 60),  // Dull tan
+                Material::Slope { .. } => (120, 150, 90), // Dull olive
+                Material::OutOfBounds => (50, 50, 50), // Dark Gray border
+                // Walkable tiles are never hit by the DDA loop above.
+                Material::Empty | Material::Water => (0, 0, 0),
+            };
 
-        // Choose wall color based on material
-        let (r, g, b) = match hit_material {
-            Material::SolidWall => (180, 0, 0),     // Red
-            Material::BrickWall => (0, 180, 0),     // Green
-            Material::StoneWall => (0, 0, 180),     // Blue
-            Material::WoodWall => (180, 180, 180),  // White
-            Material::OutOfBounds => (50, 50, 50),  // Dark Gray border
-            Material::Empty => (0, 0, 0),
-        };
-
-        // Make shadows by dimming colors (dist and side based)
-        let dim_factor = if side == 1 { 0.7 } else { 1.0 }; // N/S vs E/W
-        let dist_dim = (1.0 - (perp_wall_dist / 20.0)).max(0.1); 
-        let final_dim = dim_factor * dist_dim;
-
-        let color = Color::Rgb {
-            r: (r as f64 * final_dim) as u8,
-            g: (g as f64 * final_dim) as u8,
-            b: (b as f64 * final_dim) as u8,
-        };
-
-        // Choose ASCII character based on distance for "texture"
-        let ch = if perp_wall_dist <= 2.0 {
-            '█'
-        } else if perp_wall_dist <= 4.0 {
-            '▓'
-        } else if perp_wall_dist <= 8.0 {
-            '▒'
-        } else {
-            '░'
-        };
+            let color = Color::Rgb {
+                r: (r as f64 * final_dim) as u8,
+                g: (g as f64 * final_dim) as u8,
+                b: (b as f64 * final_dim) as u8,
+            };
+
+            // Choose ASCII character based on distance for "texture"
+            let ch = if perp_wall_dist <= 2.0 {
+                '█'
+            } else if perp_wall_dist <= 4.0 {
+                '▓'
+            } else if perp_wall_dist <= 8.0 {
+                '▒'
+            } else {
+                '░'
+            };
 
-        // Draw the vertical stripe
-        for y in draw_start..draw_end {
-            frame.set(x, y as usize, ch, color, Color::Reset);
+            for y in draw_start..draw_end {
+                frame.set(x, y as usize, ch, color, Color::Reset);
+            }
         }
     }
 }
+
+/// Picks a block glyph by brightness, giving textured walls the same
+/// "denser glyph = brighter" feel as the flat-shaded distance buckets.
+fn glyph_for_luminance(luminance: f64) -> char {
+    if luminance >= 170.0 {
+        '█'
+    } else if luminance >= 110.0 {
+        '▓'
+    } else if luminance >= 50.0 {
+        '▒'
+    } else {
+        '░'
+    }
+}
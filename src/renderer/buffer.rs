@@ -14,6 +14,9 @@ pub struct FrameBuffer {
     buffer: Vec<char>,
     fg_colors: Vec<Color>,
     bg_colors: Vec<Color>,
+    /// Per-column wall distance from the last raycast pass, used to occlude
+    /// sprites that fall behind a wall. One entry per screen column.
+    depth: Vec<f64>,
 }
 
 impl FrameBuffer {
@@ -26,6 +29,7 @@ impl FrameBuffer {
             buffer: vec![' '; size],
             fg_colors: vec![Color::Reset; size],
             bg_colors: vec![Color::Reset; size],
+            depth: vec![f64::INFINITY; width],
         }
     }
 
@@ -40,25 +44,49 @@ impl FrameBuffer {
         self.buffer.resize(size, ' ');
         self.fg_colors.resize(size, Color::Reset);
         self.bg_colors.resize(size, Color::Reset);
+        self.depth.resize(width, f64::INFINITY);
     }
 
-    /// Clears the screen, drawing a split ceiling (top) and floor (bottom) gradient.
-    pub fn clear(&mut self) {
-        let half_height = self.height / 2;
-        
-        // Ceiling (top half)
-        for y in 0..half_height {
-            for x in 0..self.width {
+    /// Scales a `water::WaterSurface` height offset (tiny spring-simulation
+    /// units) up into a visible pixel wobble of the ceiling/floor split line.
+    const WAVE_PIXEL_SCALE: f32 = 20.0;
+
+    /// Clears the screen, drawing a split ceiling (top) and floor (bottom) gradient,
+    /// and resets the per-column depth buffer so a stale wall distance can never
+    /// occlude a sprite this frame.
+    ///
+    /// `wave_offsets` lets a rippling water surface wobble the ceiling/floor
+    /// split line per column; pass an empty slice (or all zeros) for a flat
+    /// horizon.
+    pub fn clear(&mut self, wave_offsets: &[f32]) {
+        let half_height = self.height as f32 / 2.0;
+
+        for x in 0..self.width {
+            let wobble = wave_offsets.get(x).copied().unwrap_or(0.0) * Self::WAVE_PIXEL_SCALE;
+            let boundary = (half_height + wobble).round().clamp(0.0, self.height as f32) as usize;
+
+            for y in 0..boundary {
                 self.set(x, y, ' ', Color::Reset, Color::AnsiValue(234));
             }
-        }
-        
-        // Floor (bottom half)
-        for y in half_height..self.height {
-            for x in 0..self.width {
+            for y in boundary..self.height {
                 self.set(x, y, ' ', Color::Reset, Color::AnsiValue(238));
             }
         }
+
+        self.depth.fill(f64::INFINITY);
+    }
+
+    /// Records the wall distance raycast for screen column `x` this frame.
+    pub fn set_depth(&mut self, x: usize, distance: f64) {
+        if x < self.depth.len() {
+            self.depth[x] = distance;
+        }
+    }
+
+    /// The wall distance raycast for screen column `x` this frame, or
+    /// `f64::INFINITY` if nothing has been drawn in that column yet.
+    pub fn depth_at(&self, x: usize) -> f64 {
+        self.depth.get(x).copied().unwrap_or(f64::INFINITY)
     }
 
     /// Writes a character with specified colors to a specific coordinate.
@@ -0,0 +1,73 @@
+use crossterm::style::Color;
+
+use crate::math::Vector2D;
+use crate::player::Player;
+use crate::renderer::buffer::FrameBuffer;
+
+/// A billboard: always faces the player, drawn as a single glyph/color pair
+/// at a world-space position. Used for enemies, items, and other players.
+pub struct Sprite {
+    pub position: Vector2D,
+    pub glyph: char,
+    pub color: Color,
+}
+
+/// Draws `sprites` into `frame` on top of the walls raycast this frame,
+/// occluding against the per-column depth buffer `render_frame` left behind.
+///
+/// Sprites are transformed into camera space with the inverse of the camera
+/// matrix `[camera_plane.x camera_plane.y; direction.x direction.y]`, the
+/// same convention `raycaster::render_frame` uses for ray directions, so a
+/// sprite at `transform_x == 0` lines up with the center of the screen.
+pub fn render_sprites(player: &Player, sprites: &[Sprite], frame: &mut FrameBuffer) {
+    // Farthest first, so nearer sprites get drawn on top when they overlap.
+    let mut ordered: Vec<&Sprite> = sprites.iter().collect();
+    ordered.sort_by(|a, b| {
+        let dist_a = (a.position - player.position).magnitude();
+        let dist_b = (b.position - player.position).magnitude();
+        dist_b.partial_cmp(&dist_a).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let det = player.camera_plane.x * player.direction.y - player.direction.x * player.camera_plane.y;
+    let inv_det = 1.0 / det;
+
+    for sprite in ordered {
+        let relative = sprite.position - player.position;
+
+        let transform_x = inv_det * (player.direction.y * relative.x - player.direction.x * relative.y);
+        let transform_y = inv_det * (-player.camera_plane.y * relative.x + player.camera_plane.x * relative.y);
+
+        // Behind the camera; nothing to draw.
+        if transform_y <= 0.0 {
+            continue;
+        }
+
+        // Nothing on screen is ever legitimately farther out than a few
+        // screens' width/height away; a sprite this close to the camera
+        // that `transform_y` is near zero would otherwise blow `screen_x`/
+        // `sprite_size` up toward +/-infinity, and the unclamped `i32`
+        // arithmetic below them would overflow before `draw_start`/`draw_end`
+        // ever get a chance to clip it back down to the visible range.
+        let off_screen_bound = (frame.width.max(frame.height) as f64) * 4.0;
+
+        let screen_x = ((frame.width as f64 / 2.0) * (1.0 + transform_x / transform_y))
+            .clamp(-off_screen_bound, off_screen_bound) as i32;
+
+        let sprite_size = (frame.height as f64 / transform_y).abs().clamp(0.0, off_screen_bound) as i32;
+
+        let draw_start_y = (frame.height as i32 / 2 - sprite_size / 2).max(0);
+        let draw_end_y = (frame.height as i32 / 2 + sprite_size / 2).min(frame.height as i32 - 1);
+
+        let draw_start_x = (screen_x - sprite_size / 2).max(0);
+        let draw_end_x = (screen_x + sprite_size / 2).min(frame.width as i32 - 1);
+
+        for stripe in draw_start_x..draw_end_x {
+            let column = stripe as usize;
+            if transform_y < frame.depth_at(column) {
+                for y in draw_start_y..draw_end_y {
+                    frame.set(column, y as usize, sprite.glyph, sprite.color, Color::Reset);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use crossterm::style::Color;
+use image::GenericImageView;
+
+use crate::map::Material;
+
+/// Wall textures are stored as square images of this size regardless of
+/// their source resolution.
+pub const TEX_SIZE: usize = 64;
+
+/// A single wall texture, stored as `TEX_SIZE` columns of `TEX_SIZE` colors
+/// each so a raycast hit's texture column maps directly to one `Vec<Color>`.
+pub struct Texture {
+    columns: Vec<Vec<Color>>,
+}
+
+impl Texture {
+    fn from_image(path: &Path) -> Result<Self> {
+        let image = image::open(path)
+            .with_context(|| format!("failed to load wall texture {}", path.display()))?
+            .resize_exact(TEX_SIZE as u32, TEX_SIZE as u32, image::imageops::FilterType::Nearest);
+
+        let mut columns: Vec<Vec<Color>> = (0..TEX_SIZE).map(|_| Vec::with_capacity(TEX_SIZE)).collect();
+        for x in 0..TEX_SIZE as u32 {
+            for y in 0..TEX_SIZE as u32 {
+                let pixel = image.get_pixel(x, y);
+                columns[x as usize].push(Color::Rgb { r: pixel[0], g: pixel[1], b: pixel[2] });
+            }
+        }
+        Ok(Self { columns })
+    }
+
+    /// The texel at column `tex_x`, row `tex_y`, clamping both into range.
+    pub fn sample(&self, tex_x: usize, tex_y: usize) -> Color {
+        let x = tex_x.min(TEX_SIZE - 1);
+        let y = tex_y.min(TEX_SIZE - 1);
+        self.columns[x][y]
+    }
+}
+
+/// Wall textures keyed by the `Material` they're painted on. A material with
+/// no entry falls back to the original flat-shaded color in `render_frame`.
+#[derive(Default)]
+pub struct TextureSet {
+    textures: HashMap<Material, Texture>,
+}
+
+impl TextureSet {
+    /// Loads whichever of the built-in texture files exist under
+    /// `assets/textures/`. Missing files are skipped rather than treated as
+    /// an error, so the game still runs (with flat shading) on a checkout
+    /// without texture assets.
+    pub fn load_default() -> Result<Self> {
+        let mut textures = HashMap::new();
+        for (material, filename) in [
+            (Material::SolidWall, "solid.png"),
+            (Material::BrickWall, "brick.png"),
+            (Material::StoneWall, "stone.png"),
+            (Material::WoodWall, "wood.png"),
+        ] {
+            let path = Path::new("assets/textures").join(filename);
+            if path.exists() {
+                textures.insert(material, Texture::from_image(&path)?);
+            }
+        }
+        Ok(Self { textures })
+    }
+
+    /// The texture for `material`, if one was loaded.
+    pub fn get(&self, material: Material) -> Option<&Texture> {
+        self.textures.get(&material)
+    }
+}
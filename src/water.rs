@@ -0,0 +1,82 @@
+//! Animated liquid surfaces, simulated with the classic "column spring"
+//! technique: each column is a damped spring pulling back toward a rest
+//! height, and disturbances spread to neighboring columns every tick.
+
+/// How strongly a column is pulled back toward its rest height.
+pub const TENSION: f32 = 0.025;
+/// How quickly a column's own oscillation decays.
+pub const DAMPENING: f32 = 0.025;
+/// How much of a column's height difference from a neighbor propagates
+/// into that neighbor's velocity each tick.
+pub const SPREAD: f32 = 0.2;
+
+/// A 1D chain of spring-damper columns modeling one rippling liquid surface.
+pub struct WaterSurface {
+    height: Vec<f32>,
+    velocity: Vec<f32>,
+    target: f32,
+}
+
+impl WaterSurface {
+    /// Creates a surface of `columns` columns at rest at `target` height.
+    pub fn new(columns: usize, target: f32) -> Self {
+        Self {
+            height: vec![target; columns],
+            velocity: vec![0.0; columns],
+            target,
+        }
+    }
+
+    /// Resizes the surface, keeping existing columns' state and settling any
+    /// newly added columns at rest.
+    pub fn resize(&mut self, columns: usize) {
+        self.height.resize(columns, self.target);
+        self.velocity.resize(columns, 0.0);
+    }
+
+    /// Advances the simulation by one tick: every column springs back toward
+    /// `target`, then the resulting height differences are spread to each
+    /// column's immediate neighbors.
+    pub fn tick(&mut self) {
+        let n = self.height.len();
+        for i in 0..n {
+            self.velocity[i] += TENSION * (self.target - self.height[i]) - DAMPENING * self.velocity[i];
+            self.height[i] += self.velocity[i];
+        }
+
+        // Scratch deltas so the spread pass reads only pre-pass heights,
+        // rather than heights some earlier iteration of this same pass changed.
+        let mut left_delta = vec![0.0; n];
+        let mut right_delta = vec![0.0; n];
+        for i in 0..n {
+            if i > 0 {
+                left_delta[i] = SPREAD * (self.height[i] - self.height[i - 1]);
+            }
+            if i + 1 < n {
+                right_delta[i] = SPREAD * (self.height[i] - self.height[i + 1]);
+            }
+        }
+        for i in 0..n {
+            if i > 0 {
+                self.velocity[i - 1] += left_delta[i];
+            }
+            if i + 1 < n {
+                self.velocity[i + 1] += right_delta[i];
+            }
+        }
+    }
+
+    /// Injects a velocity impulse at the column nearest `column`, e.g. from a
+    /// player stepping into the liquid.
+    pub fn splash(&mut self, column: usize, impulse: f32) {
+        if let Some(v) = self.velocity.get_mut(column.min(self.height.len().saturating_sub(1))) {
+            *v += impulse;
+        }
+    }
+
+    /// The current height offset for `column`, relative to rest.
+    pub fn offset_at(&self, column: usize) -> f32 {
+        let idx = column.min(self.height.len().saturating_sub(1));
+        self.height.get(idx).copied().unwrap_or(self.target) - self.target
+    }
+}